@@ -0,0 +1,78 @@
+//! Decompression dispatch for the compression methods ZIP entries use:
+//! stored (0), deflate (8), bzip2 (12) and zstd (93).
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATE: u16 = 8;
+const METHOD_BZIP2: u16 = 12;
+const METHOD_ZSTD: u16 = 93;
+
+/// Decompresses `data` per `compression_method`, returning an error
+/// describing why decompression failed (or that the method is
+/// unsupported) instead of silently discarding the entry.
+pub fn decompress(data: &[u8], compression_method: u16) -> Result<Vec<u8>, String> {
+    match compression_method {
+        METHOD_STORED => Ok(data.to_vec()),
+        METHOD_DEFLATE => miniz_oxide::inflate::decompress_to_vec(data)
+            .map_err(|e| format!("deflate decompression failed: {:?}", e)),
+        METHOD_BZIP2 => decompress_bzip2(data),
+        METHOD_ZSTD => decompress_zstd(data),
+        other => Err(format!("unsupported compression method {}", other)),
+    }
+}
+
+fn decompress_bzip2(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decompressed = Vec::new();
+    let mut decoder = bzip2_rs::DecoderReader::new(data);
+    std::io::Read::read_to_end(&mut decoder, &mut decompressed)
+        .map(|_| decompressed)
+        .map_err(|e| format!("bzip2 decompression failed: {}", e))
+}
+
+fn decompress_zstd(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decoder = ruzstd::StreamingDecoder::new(data)
+        .map_err(|e| format!("zstd decompression failed: {}", e))?;
+    let mut decompressed = Vec::new();
+    std::io::Read::read_to_end(&mut decoder, &mut decompressed)
+        .map(|_| decompressed)
+        .map_err(|e| format!("zstd decompression failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `{"a":1}` compressed with the reference `zstd` CLI.
+    const ZSTD_JSON: [u8; 20] = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x24, 0x07, 0x39, 0x00, 0x00, 0x7b, 0x22, 0x61, 0x22, 0x3a, 0x31,
+        0x7d, 0x48, 0x8b, 0xfc, 0x32,
+    ];
+
+    // `{"a":1}` compressed with the reference `bzip2` CLI.
+    const BZIP2_JSON: [u8; 46] = [
+        0x42, 0x5a, 0x68, 0x39, 0x31, 0x41, 0x59, 0x26, 0x53, 0x59, 0x3a, 0xdf, 0x03, 0x60, 0x00,
+        0x00, 0x02, 0x99, 0x80, 0x10, 0x00, 0x20, 0x10, 0x20, 0x00, 0x00, 0x0a, 0x20, 0x00, 0x21,
+        0x80, 0x0c, 0x02, 0x5b, 0x06, 0xdc, 0x5d, 0xc9, 0x14, 0xe1, 0x42, 0x40, 0xeb, 0x7c, 0x0d,
+        0x80,
+    ];
+
+    #[test]
+    fn decompress_stored_returns_data_unchanged() {
+        let data = b"{\"a\":1}";
+        assert_eq!(decompress(data, METHOD_STORED).unwrap(), data);
+    }
+
+    #[test]
+    fn decompress_zstd_round_trips_real_payload() {
+        assert_eq!(decompress(&ZSTD_JSON, METHOD_ZSTD).unwrap(), b"{\"a\":1}");
+    }
+
+    #[test]
+    fn decompress_bzip2_round_trips_real_payload() {
+        assert_eq!(decompress(&BZIP2_JSON, METHOD_BZIP2).unwrap(), b"{\"a\":1}");
+    }
+
+    #[test]
+    fn decompress_rejects_unsupported_method() {
+        assert!(decompress(b"", 99).is_err());
+    }
+}
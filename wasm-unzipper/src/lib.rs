@@ -1,65 +1,624 @@
-use wasm_bindgen::prelude::*;
-use miniz_oxide::inflate::decompress_to_vec_zlib;
+mod compression;
+mod crypto;
+
 use serde_json::Value;
-use serde_wasm_bindgen;
+use wasm_bindgen::prelude::*;
 
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
-#[wasm_bindgen]
-pub fn extract_jsons(zip_bytes: &[u8]) -> JsValue {
+const EOCD_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
+const CENTRAL_DIRECTORY_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x01, 0x02];
+const LOCAL_FILE_HEADER_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x03, 0x04];
+const ZIP64_EOCD_LOCATOR_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x06, 0x07];
+const ZIP64_EOCD_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x06, 0x06];
+const ZIP64_EXTRA_FIELD_ID: u16 = 0x0001;
+
+const EOCD_MIN_SIZE: usize = 22;
+const EOCD_MAX_COMMENT_LEN: usize = 65535;
+const ZIP64_EOCD_LOCATOR_SIZE: usize = 20;
+const MIN_CENTRAL_DIRECTORY_RECORD_SIZE: usize = 46;
+
+const SENTINEL_32: u32 = 0xFFFFFFFF;
+const SENTINEL_16: u16 = 0xFFFF;
+
+const WINZIP_AES_COMPRESSION_METHOD: u16 = 99;
+const GPBIT_ENCRYPTED: u16 = 0x0001;
+const GPBIT_DATA_DESCRIPTOR: u16 = 0x0008;
+
+struct CentralDirectoryLocation {
+    entry_count: usize,
+    central_directory_offset: usize,
+}
+
+struct CentralDirectoryEntry {
+    compression_method: u16,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    file_name: String,
+    local_header_offset: usize,
+    general_purpose_flag: u16,
+    last_mod_time: u16,
+    crc32: u32,
+    extra_field: Vec<u8>,
+}
+
+/// One central-directory entry's extraction outcome. `name` and the size
+/// fields come straight from the directory record regardless of how
+/// extraction went, so the caller always has them even when `parsed` is
+/// `None` and `error` explains what went wrong (bad offset, failed
+/// decryption, unsupported compression, invalid JSON).
+#[derive(serde::Serialize)]
+struct JsonEntry {
+    name: String,
+    uncompressed_size: u64,
+    compression_method: u16,
+    parsed: Option<Value>,
+    error: Option<String>,
+}
+
+/// The overall outcome of `extract_jsons`. `archive_error` is set only when
+/// the archive itself couldn't be parsed at all (no EOCD record, central
+/// directory not locatable, or a central-directory record that doesn't line
+/// up) — an empty `entries` with `archive_error` absent instead means the
+/// archive parsed fine but contained no `.json` entries.
+#[derive(serde::Serialize)]
+struct ExtractionResult {
+    entries: Vec<JsonEntry>,
+    archive_error: Option<String>,
+}
+
+/// Scans backward from the end of the buffer for the End Of Central Directory
+/// record, accounting for the variable-length trailing comment.
+fn find_end_of_central_directory(zip_bytes: &[u8]) -> Option<usize> {
+    if zip_bytes.len() < EOCD_MIN_SIZE {
+        return None;
+    }
+
+    let search_start = zip_bytes
+        .len()
+        .saturating_sub(EOCD_MIN_SIZE + EOCD_MAX_COMMENT_LEN);
+    let mut pos = zip_bytes.len() - EOCD_MIN_SIZE;
+
+    loop {
+        if zip_bytes[pos..pos + 4] == EOCD_SIGNATURE {
+            return Some(pos);
+        }
+        if pos == search_start {
+            break;
+        }
+        pos -= 1;
+    }
+
+    None
+}
+
+/// Locates the central directory, preferring the ZIP64 End Of Central
+/// Directory record (via its locator, which sits immediately before the
+/// regular EOCD record) when the regular record's fields are sentinels.
+fn locate_central_directory(
+    zip_bytes: &[u8],
+    eocd_offset: usize,
+) -> Option<CentralDirectoryLocation> {
+    let entry_count_16 =
+        u16::from_le_bytes([zip_bytes[eocd_offset + 10], zip_bytes[eocd_offset + 11]]);
+    let central_directory_offset_32 = u32::from_le_bytes([
+        zip_bytes[eocd_offset + 16],
+        zip_bytes[eocd_offset + 17],
+        zip_bytes[eocd_offset + 18],
+        zip_bytes[eocd_offset + 19],
+    ]);
+
+    if entry_count_16 != SENTINEL_16 && central_directory_offset_32 != SENTINEL_32 {
+        return Some(CentralDirectoryLocation {
+            entry_count: entry_count_16 as usize,
+            central_directory_offset: central_directory_offset_32 as usize,
+        });
+    }
+
+    let locator_offset = eocd_offset.checked_sub(ZIP64_EOCD_LOCATOR_SIZE)?;
+    if zip_bytes[locator_offset..locator_offset + 4] != ZIP64_EOCD_LOCATOR_SIGNATURE {
+        return None;
+    }
+
+    let zip64_eocd_offset = u64::from_le_bytes(
+        zip_bytes[locator_offset + 8..locator_offset + 16]
+            .try_into()
+            .ok()?,
+    ) as usize;
+    if zip64_eocd_offset + 56 > zip_bytes.len()
+        || zip_bytes[zip64_eocd_offset..zip64_eocd_offset + 4] != ZIP64_EOCD_SIGNATURE
+    {
+        return None;
+    }
+
+    let entry_count = u64::from_le_bytes(
+        zip_bytes[zip64_eocd_offset + 32..zip64_eocd_offset + 40]
+            .try_into()
+            .ok()?,
+    ) as usize;
+    let central_directory_offset = u64::from_le_bytes(
+        zip_bytes[zip64_eocd_offset + 48..zip64_eocd_offset + 56]
+            .try_into()
+            .ok()?,
+    ) as usize;
+
+    Some(CentralDirectoryLocation {
+        entry_count,
+        central_directory_offset,
+    })
+}
+
+/// Reads the ZIP64 extended-information extra field (header ID `0x0001`),
+/// which carries the real 8-byte values for whichever of uncompressed size,
+/// compressed size, local-header offset and disk number were sentinels in
+/// the fixed-width central directory record, in that fixed order.
+fn read_zip64_extra_field(
+    extra_field: &[u8],
+    uncompressed_size_is_sentinel: bool,
+    compressed_size_is_sentinel: bool,
+    local_header_offset_is_sentinel: bool,
+) -> Option<(Option<u64>, Option<u64>, Option<u64>)> {
     let mut pos = 0;
-    let mut results = Vec::new();
+    while pos + 4 <= extra_field.len() {
+        let header_id = u16::from_le_bytes([extra_field[pos], extra_field[pos + 1]]);
+        let data_size = u16::from_le_bytes([extra_field[pos + 2], extra_field[pos + 3]]) as usize;
+        let data_start = pos + 4;
+        let data_end = data_start + data_size;
+        if data_end > extra_field.len() {
+            return None;
+        }
 
-    while pos + 30 < zip_bytes.len() {
-        // Check for local file header signature
-        if &zip_bytes[pos..pos + 4] != [0x50, 0x4b, 0x03, 0x04] {
-            pos += 1;
-            continue;
+        if header_id == ZIP64_EXTRA_FIELD_ID {
+            let data = &extra_field[data_start..data_end];
+            let mut field_pos = 0;
+            let mut read_u64 = |present: bool| -> Option<u64> {
+                if !present {
+                    return None;
+                }
+                let value =
+                    u64::from_le_bytes(data.get(field_pos..field_pos + 8)?.try_into().ok()?);
+                field_pos += 8;
+                Some(value)
+            };
+
+            let uncompressed_size = read_u64(uncompressed_size_is_sentinel);
+            let compressed_size = read_u64(compressed_size_is_sentinel);
+            let local_header_offset = read_u64(local_header_offset_is_sentinel);
+            return Some((uncompressed_size, compressed_size, local_header_offset));
+        }
+
+        pos = data_end;
+    }
+
+    None
+}
+
+/// Parses the central directory into an ordered list of entries, using the
+/// offset and count recorded in the (possibly ZIP64) End Of Central
+/// Directory record.
+fn read_central_directory(
+    zip_bytes: &[u8],
+    location: &CentralDirectoryLocation,
+) -> Option<Vec<CentralDirectoryEntry>> {
+    // An entry count claiming more records than could possibly fit in the
+    // buffer is a malformed (or hostile) EOCD; reject it before it reaches
+    // `Vec::with_capacity`, which would otherwise let an attacker-controlled
+    // count drive an unbounded allocation.
+    let max_possible_entries = zip_bytes.len() / MIN_CENTRAL_DIRECTORY_RECORD_SIZE;
+    if location.entry_count > max_possible_entries {
+        return None;
+    }
+
+    let mut entries = Vec::with_capacity(location.entry_count);
+    let mut pos = location.central_directory_offset;
+
+    for _ in 0..location.entry_count {
+        if pos + 46 > zip_bytes.len() || zip_bytes[pos..pos + 4] != CENTRAL_DIRECTORY_SIGNATURE {
+            return None;
         }
 
-        // Parse local file header
-        let file_name_len = u16::from_le_bytes([zip_bytes[pos + 26], zip_bytes[pos + 27]]) as usize;
-        let extra_len = u16::from_le_bytes([zip_bytes[pos + 28], zip_bytes[pos + 29]]) as usize;
-        let compressed_size = u32::from_le_bytes([
+        let general_purpose_flag = u16::from_le_bytes([zip_bytes[pos + 8], zip_bytes[pos + 9]]);
+        let compression_method = u16::from_le_bytes([zip_bytes[pos + 10], zip_bytes[pos + 11]]);
+        let last_mod_time = u16::from_le_bytes([zip_bytes[pos + 12], zip_bytes[pos + 13]]);
+        let crc32 = u32::from_le_bytes([
+            zip_bytes[pos + 16],
+            zip_bytes[pos + 17],
             zip_bytes[pos + 18],
             zip_bytes[pos + 19],
+        ]);
+        let uncompressed_size_32 = u32::from_le_bytes([
+            zip_bytes[pos + 24],
+            zip_bytes[pos + 25],
+            zip_bytes[pos + 26],
+            zip_bytes[pos + 27],
+        ]);
+        let compressed_size_32 = u32::from_le_bytes([
             zip_bytes[pos + 20],
             zip_bytes[pos + 21],
-        ]) as usize;
-        let compression_method = u16::from_le_bytes([zip_bytes[pos + 8], zip_bytes[pos + 9]]);
+            zip_bytes[pos + 22],
+            zip_bytes[pos + 23],
+        ]);
+        let file_name_len = u16::from_le_bytes([zip_bytes[pos + 28], zip_bytes[pos + 29]]) as usize;
+        let extra_len = u16::from_le_bytes([zip_bytes[pos + 30], zip_bytes[pos + 31]]) as usize;
+        let comment_len = u16::from_le_bytes([zip_bytes[pos + 32], zip_bytes[pos + 33]]) as usize;
+        let local_header_offset_32 = u32::from_le_bytes([
+            zip_bytes[pos + 42],
+            zip_bytes[pos + 43],
+            zip_bytes[pos + 44],
+            zip_bytes[pos + 45],
+        ]);
 
-        let name_start = pos + 30;
+        let name_start = pos + 46;
         let name_end = name_start + file_name_len;
-        if name_end > zip_bytes.len() {
-            break;
+        let extra_start = name_end;
+        let extra_end = extra_start + extra_len;
+        if extra_end > zip_bytes.len() {
+            return None;
         }
-        let file_name = &zip_bytes[name_start..name_end];
-        let file_name_str = String::from_utf8_lossy(file_name);
+        let file_name = String::from_utf8_lossy(&zip_bytes[name_start..name_end]).into_owned();
 
-        let data_start = name_end + extra_len;
-        let data_end = data_start + compressed_size;
-        if data_end > zip_bytes.len() {
-            break;
-        }
-        let compressed_data = &zip_bytes[data_start..data_end];
-
-        if file_name_str.ends_with(".json") {
-            let decompressed = if compression_method == 0 {
-                compressed_data.to_vec()
-            } else if compression_method == 8 {
-                miniz_oxide::inflate::decompress_to_vec(compressed_data).unwrap_or_default()
-            } else {
-                Vec::new()
-            };
+        let mut compressed_size = compressed_size_32 as u64;
+        let mut uncompressed_size = uncompressed_size_32 as u64;
+        let mut local_header_offset = local_header_offset_32 as u64;
 
-            if let Ok(json) = serde_json::from_slice::<Value>(&decompressed) {
-                results.push(json);
+        if compressed_size_32 == SENTINEL_32
+            || uncompressed_size_32 == SENTINEL_32
+            || local_header_offset_32 == SENTINEL_32
+        {
+            if let Some((
+                zip64_uncompressed_size,
+                zip64_compressed_size,
+                zip64_local_header_offset,
+            )) = read_zip64_extra_field(
+                &zip_bytes[extra_start..extra_end],
+                uncompressed_size_32 == SENTINEL_32,
+                compressed_size_32 == SENTINEL_32,
+                local_header_offset_32 == SENTINEL_32,
+            ) {
+                if let Some(value) = zip64_uncompressed_size {
+                    uncompressed_size = value;
+                }
+                if let Some(value) = zip64_compressed_size {
+                    compressed_size = value;
+                }
+                if let Some(value) = zip64_local_header_offset {
+                    local_header_offset = value;
+                }
             }
         }
 
-        pos = data_end;
+        entries.push(CentralDirectoryEntry {
+            compression_method,
+            compressed_size,
+            uncompressed_size,
+            file_name,
+            local_header_offset: local_header_offset as usize,
+            general_purpose_flag,
+            last_mod_time,
+            crc32,
+            extra_field: zip_bytes[extra_start..extra_end].to_vec(),
+        });
+
+        pos = extra_end + comment_len;
+    }
+
+    Some(entries)
+}
+
+/// Reads the compressed byte range for an entry by seeking to its local
+/// header and skipping past the name and extra fields recorded there.
+fn read_local_file_data<'a>(
+    zip_bytes: &'a [u8],
+    entry: &CentralDirectoryEntry,
+) -> Option<&'a [u8]> {
+    let pos = entry.local_header_offset;
+    if pos + 30 > zip_bytes.len() || zip_bytes[pos..pos + 4] != LOCAL_FILE_HEADER_SIGNATURE {
+        return None;
     }
 
-    serde_wasm_bindgen::to_value(&results).unwrap()
+    let file_name_len = u16::from_le_bytes([zip_bytes[pos + 26], zip_bytes[pos + 27]]) as usize;
+    let extra_len = u16::from_le_bytes([zip_bytes[pos + 28], zip_bytes[pos + 29]]) as usize;
+
+    let compressed_size = usize::try_from(entry.compressed_size).ok()?;
+    let data_start = pos + 30 + file_name_len + extra_len;
+    let data_end = data_start + compressed_size;
+    if data_end > zip_bytes.len() {
+        return None;
+    }
+
+    Some(&zip_bytes[data_start..data_end])
+}
+
+/// Decrypts an entry's raw local-file bytes if it is encrypted, returning
+/// the plaintext together with the compression method that now applies to
+/// it (WinZip AES wraps the real method inside its `0x9901` extra field).
+fn decrypt_entry(
+    compressed_data: &[u8],
+    entry: &CentralDirectoryEntry,
+    password: Option<&str>,
+) -> Result<(Vec<u8>, u16), String> {
+    if entry.general_purpose_flag & GPBIT_ENCRYPTED == 0 {
+        return Ok((compressed_data.to_vec(), entry.compression_method));
+    }
+
+    let password =
+        password.ok_or_else(|| "entry is encrypted but no password was supplied".to_string())?;
+
+    if entry.compression_method == WINZIP_AES_COMPRESSION_METHOD {
+        let aes_info = crypto::read_aes_extra_field(&entry.extra_field)
+            .ok_or_else(|| "missing or malformed AES extra field".to_string())?;
+        let plaintext = crypto::decrypt_winzip_aes(compressed_data, aes_info.strength, password)
+            .ok_or_else(|| "AES decryption or authentication failed".to_string())?;
+        Ok((plaintext, aes_info.actual_compression_method))
+    } else {
+        let check_byte = if entry.general_purpose_flag & GPBIT_DATA_DESCRIPTOR != 0 {
+            ((entry.last_mod_time >> 8) & 0xff) as u8
+        } else {
+            ((entry.crc32 >> 24) & 0xff) as u8
+        };
+        let plaintext = crypto::decrypt_zip_crypto(compressed_data, password, check_byte)
+            .ok_or_else(|| "ZipCrypto decryption failed".to_string())?;
+        Ok((plaintext, entry.compression_method))
+    }
+}
+
+/// Builds the extraction outcome for one entry: reads its local-file data,
+/// decrypts it if needed, decompresses it, then parses it as JSON, turning
+/// the first failure encountered into `error` instead of dropping the
+/// entry, so the caller can tell "no matching files" apart from
+/// "a matching file failed to extract".
+fn extract_entry(
+    zip_bytes: &[u8],
+    entry: &CentralDirectoryEntry,
+    password: Option<&str>,
+) -> JsonEntry {
+    let failure = |error: String, compression_method: u16| JsonEntry {
+        name: entry.file_name.clone(),
+        uncompressed_size: entry.uncompressed_size,
+        compression_method,
+        parsed: None,
+        error: Some(error),
+    };
+
+    let raw_data = match read_local_file_data(zip_bytes, entry) {
+        Some(data) => data,
+        None => {
+            return failure(
+                "local file header offset is out of bounds or malformed".to_string(),
+                entry.compression_method,
+            )
+        }
+    };
+
+    let (compressed_data, compression_method) = match decrypt_entry(raw_data, entry, password) {
+        Ok(result) => result,
+        Err(error) => return failure(error, entry.compression_method),
+    };
+
+    let decompressed = match compression::decompress(&compressed_data, compression_method) {
+        Ok(data) => data,
+        Err(error) => return failure(error, compression_method),
+    };
+
+    match serde_json::from_slice::<Value>(&decompressed) {
+        Ok(parsed) => JsonEntry {
+            name: entry.file_name.clone(),
+            uncompressed_size: entry.uncompressed_size,
+            compression_method,
+            parsed: Some(parsed),
+            error: None,
+        },
+        Err(error) => failure(format!("invalid JSON: {}", error), compression_method),
+    }
+}
+
+#[wasm_bindgen]
+pub fn extract_jsons(zip_bytes: &[u8], password: Option<String>) -> JsValue {
+    let archive_error = |error: String| ExtractionResult {
+        entries: Vec::new(),
+        archive_error: Some(error),
+    };
+
+    let eocd_offset = match find_end_of_central_directory(zip_bytes) {
+        Some(offset) => offset,
+        None => {
+            return serde_wasm_bindgen::to_value(&archive_error(
+                "end of central directory record not found".to_string(),
+            ))
+            .unwrap()
+        }
+    };
+
+    let central_directory_location = match locate_central_directory(zip_bytes, eocd_offset) {
+        Some(location) => location,
+        None => {
+            return serde_wasm_bindgen::to_value(&archive_error(
+                "central directory could not be located".to_string(),
+            ))
+            .unwrap()
+        }
+    };
+
+    let entries = match read_central_directory(zip_bytes, &central_directory_location) {
+        Some(entries) => entries,
+        None => {
+            return serde_wasm_bindgen::to_value(&archive_error(
+                "central directory is malformed or truncated".to_string(),
+            ))
+            .unwrap()
+        }
+    };
+
+    let mut results: Vec<JsonEntry> = Vec::new();
+    for entry in &entries {
+        if !entry.file_name.ends_with(".json") {
+            continue;
+        }
+
+        results.push(extract_entry(zip_bytes, entry, password.as_deref()));
+    }
+
+    serde_wasm_bindgen::to_value(&ExtractionResult {
+        entries: results,
+        archive_error: None,
+    })
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_zip64_extra_field_rejects_truncated_block() {
+        // Header ID + data_size claim an 8-byte value, but only 4 bytes follow.
+        let mut extra = Vec::new();
+        extra.extend_from_slice(&ZIP64_EXTRA_FIELD_ID.to_le_bytes());
+        extra.extend_from_slice(&8u16.to_le_bytes());
+        extra.extend_from_slice(&[0u8; 4]);
+
+        assert!(read_zip64_extra_field(&extra, true, false, false).is_none());
+    }
+
+    #[test]
+    fn read_zip64_extra_field_parses_present_fields_in_order() {
+        let mut extra = Vec::new();
+        extra.extend_from_slice(&ZIP64_EXTRA_FIELD_ID.to_le_bytes());
+        extra.extend_from_slice(&16u16.to_le_bytes());
+        extra.extend_from_slice(&42u64.to_le_bytes()); // uncompressed size
+        extra.extend_from_slice(&7u64.to_le_bytes()); // compressed size
+
+        let (uncompressed, compressed, offset) =
+            read_zip64_extra_field(&extra, true, true, false).unwrap();
+        assert_eq!(uncompressed, Some(42));
+        assert_eq!(compressed, Some(7));
+        assert_eq!(offset, None);
+    }
+
+    /// Builds a minimal one-entry ZIP (stored, no data descriptor) whose
+    /// central directory record uses ZIP64 sentinels for both sizes, with
+    /// the real sizes carried in a `0x0001` extra field, and checks that
+    /// parsing the archive recovers the authoritative sizes and file data.
+    #[test]
+    fn central_directory_round_trips_zip64_sized_entry() {
+        let file_name = b"a.json";
+        let file_data = b"{}";
+
+        let mut local_header = Vec::new();
+        local_header.extend_from_slice(&LOCAL_FILE_HEADER_SIGNATURE);
+        local_header.extend_from_slice(&[0u8; 22]); // version..uncompressed size, unused by the parser
+        local_header.extend_from_slice(&(file_name.len() as u16).to_le_bytes());
+        local_header.extend_from_slice(&0u16.to_le_bytes()); // extra len
+        local_header.extend_from_slice(file_name);
+        local_header.extend_from_slice(file_data);
+
+        let mut zip64_extra = Vec::new();
+        zip64_extra.extend_from_slice(&ZIP64_EXTRA_FIELD_ID.to_le_bytes());
+        zip64_extra.extend_from_slice(&16u16.to_le_bytes());
+        zip64_extra.extend_from_slice(&(file_data.len() as u64).to_le_bytes());
+        zip64_extra.extend_from_slice(&(file_data.len() as u64).to_le_bytes());
+
+        let central_directory_offset = local_header.len();
+        let mut central_directory = Vec::new();
+        central_directory.extend_from_slice(&CENTRAL_DIRECTORY_SIGNATURE);
+        central_directory.extend_from_slice(&[0u8; 4]); // version made by, version needed
+        central_directory.extend_from_slice(&[0u8; 2]); // general purpose flag
+        central_directory.extend_from_slice(&[0u8; 2]); // compression method: stored
+        central_directory.extend_from_slice(&[0u8; 4]); // last mod time/date
+        central_directory.extend_from_slice(&[0u8; 4]); // crc32
+        central_directory.extend_from_slice(&SENTINEL_32.to_le_bytes()); // compressed size
+        central_directory.extend_from_slice(&SENTINEL_32.to_le_bytes()); // uncompressed size
+        central_directory.extend_from_slice(&(file_name.len() as u16).to_le_bytes());
+        central_directory.extend_from_slice(&(zip64_extra.len() as u16).to_le_bytes());
+        central_directory.extend_from_slice(&0u16.to_le_bytes()); // comment len
+        central_directory.extend_from_slice(&[0u8; 4]); // disk number, internal attrs
+        central_directory.extend_from_slice(&[0u8; 4]); // external attrs
+        central_directory.extend_from_slice(&0u32.to_le_bytes()); // local header offset
+        central_directory.extend_from_slice(file_name);
+        central_directory.extend_from_slice(&zip64_extra);
+
+        let eocd_offset = local_header.len() + central_directory.len();
+        let mut eocd = Vec::new();
+        eocd.extend_from_slice(&EOCD_SIGNATURE);
+        eocd.extend_from_slice(&[0u8; 4]); // disk numbers
+        eocd.extend_from_slice(&1u16.to_le_bytes()); // entries on this disk
+        eocd.extend_from_slice(&1u16.to_le_bytes()); // total entries
+        eocd.extend_from_slice(&(central_directory.len() as u32).to_le_bytes());
+        eocd.extend_from_slice(&(central_directory_offset as u32).to_le_bytes());
+        eocd.extend_from_slice(&0u16.to_le_bytes()); // comment len
+
+        let mut zip_bytes = local_header.clone();
+        zip_bytes.extend_from_slice(&central_directory);
+        zip_bytes.extend_from_slice(&eocd);
+
+        let found_eocd_offset = find_end_of_central_directory(&zip_bytes).unwrap();
+        assert_eq!(found_eocd_offset, eocd_offset);
+
+        let location = locate_central_directory(&zip_bytes, found_eocd_offset).unwrap();
+        let entries = read_central_directory(&zip_bytes, &location).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].uncompressed_size, file_data.len() as u64);
+        assert_eq!(entries[0].compressed_size, file_data.len() as u64);
+
+        let entry_data = read_local_file_data(&zip_bytes, &entries[0]).unwrap();
+        assert_eq!(entry_data, file_data);
+    }
+
+    /// A ZIP64 EOCD claiming far more entries than the buffer could possibly
+    /// hold must be rejected as malformed, not turned into a multi-terabyte
+    /// `Vec::with_capacity` call.
+    #[test]
+    fn read_central_directory_rejects_implausible_zip64_entry_count() {
+        let location = CentralDirectoryLocation {
+            entry_count: usize::MAX / 2,
+            central_directory_offset: 0,
+        };
+        let zip_bytes = [0u8; 128];
+
+        assert!(read_central_directory(&zip_bytes, &location).is_none());
+    }
+
+    /// On the wasm32 target this crate ships to, `usize` is 32 bits, so a
+    /// ZIP64 entry's size must be carried as `u64` all the way to
+    /// `CentralDirectoryEntry`/`JsonEntry` — never narrowed — or a
+    /// multi-gigabyte entry silently wraps mod 2^32.
+    #[test]
+    fn central_directory_preserves_sizes_beyond_u32_max() {
+        let file_name = b"huge.json";
+        let huge_size: u64 = (u32::MAX as u64) + 1234;
+
+        let mut zip64_extra = Vec::new();
+        zip64_extra.extend_from_slice(&ZIP64_EXTRA_FIELD_ID.to_le_bytes());
+        zip64_extra.extend_from_slice(&16u16.to_le_bytes());
+        zip64_extra.extend_from_slice(&huge_size.to_le_bytes()); // uncompressed size
+        zip64_extra.extend_from_slice(&huge_size.to_le_bytes()); // compressed size
+
+        let mut central_directory = Vec::new();
+        central_directory.extend_from_slice(&CENTRAL_DIRECTORY_SIGNATURE);
+        central_directory.extend_from_slice(&[0u8; 4]); // version made by, version needed
+        central_directory.extend_from_slice(&[0u8; 2]); // general purpose flag
+        central_directory.extend_from_slice(&[0u8; 2]); // compression method: stored
+        central_directory.extend_from_slice(&[0u8; 4]); // last mod time/date
+        central_directory.extend_from_slice(&[0u8; 4]); // crc32
+        central_directory.extend_from_slice(&SENTINEL_32.to_le_bytes()); // compressed size
+        central_directory.extend_from_slice(&SENTINEL_32.to_le_bytes()); // uncompressed size
+        central_directory.extend_from_slice(&(file_name.len() as u16).to_le_bytes());
+        central_directory.extend_from_slice(&(zip64_extra.len() as u16).to_le_bytes());
+        central_directory.extend_from_slice(&0u16.to_le_bytes()); // comment len
+        central_directory.extend_from_slice(&[0u8; 4]); // disk number, internal attrs
+        central_directory.extend_from_slice(&[0u8; 4]); // external attrs
+        central_directory.extend_from_slice(&0u32.to_le_bytes()); // local header offset
+        central_directory.extend_from_slice(file_name);
+        central_directory.extend_from_slice(&zip64_extra);
+
+        let location = CentralDirectoryLocation {
+            entry_count: 1,
+            central_directory_offset: 0,
+        };
+
+        let entries = read_central_directory(&central_directory, &location).unwrap();
+        assert_eq!(entries[0].uncompressed_size, huge_size);
+        assert_eq!(entries[0].compressed_size, huge_size);
+    }
 }
@@ -1,40 +1,162 @@
-use wasm_bindgen::prelude::*;
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use serde_json::Value;
-use serde_wasm_bindgen;
-use zip::ZipArchive;
 use std::io::Cursor;
 use std::io::Read;
+use wasm_bindgen::prelude::*;
+use zip::ZipArchive;
 
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
+/// Extraction outcome for one entry the `EntrySelector` matched and
+/// `ZipArchive` could open. `parsed` and `error` are mutually exclusive: a
+/// read or JSON-parse failure on this entry is recorded here rather than
+/// aborting extraction of the rest of the archive. An entry that
+/// `ZipArchive::by_index` rejects (an encrypted entry, or one using a
+/// compression method this build does not decode) is skipped and gets no
+/// record.
+#[derive(serde::Serialize)]
+struct JsonEntry {
+    name: String,
+    uncompressed_size: u64,
+    compression_method: u16,
+    parsed: Option<Value>,
+    error: Option<String>,
+}
+
+/// The overall outcome of `extract_jsons`. `archive_error` covers the ways
+/// extraction can fail before any entry is even looked at: a bad ZIP
+/// signature, an invalid glob pattern, or `ZipArchive` itself rejecting the
+/// bytes. With it absent, an empty `entries` means that no entry both
+/// matched the selector and could be opened by `ZipArchive`.
+#[derive(serde::Serialize)]
+struct ExtractionResult {
+    entries: Vec<JsonEntry>,
+    archive_error: Option<String>,
+}
+
+/// Selects which archive entries to extract. Defaults to the legacy policy
+/// (`.json` files under `results/`, skipping `invalid-issues.json` and
+/// `__MACOSX`/`.DS_Store`) when no patterns are supplied; otherwise matches
+/// each entry name against the include globs, minus any `!`-prefixed
+/// exclude globs. Patterns consisting only of excludes (no plain include
+/// glob) match everything except what's excluded, rather than nothing.
+enum EntrySelector {
+    Default,
+    Patterns {
+        includes: GlobSet,
+        excludes: GlobSet,
+    },
+}
+
+impl EntrySelector {
+    fn from_patterns(patterns: Option<Vec<String>>) -> Result<Self, globset::Error> {
+        let patterns = match patterns {
+            Some(patterns) if !patterns.is_empty() => patterns,
+            _ => return Ok(EntrySelector::Default),
+        };
+
+        let mut includes = GlobSetBuilder::new();
+        let mut excludes = GlobSetBuilder::new();
+        for pattern in &patterns {
+            if let Some(exclude_pattern) = pattern.strip_prefix('!') {
+                excludes.add(Glob::new(exclude_pattern)?);
+            } else {
+                includes.add(Glob::new(pattern)?);
+            }
+        }
+
+        Ok(EntrySelector::Patterns {
+            includes: includes.build()?,
+            excludes: excludes.build()?,
+        })
+    }
+
+    fn is_match(&self, name: &str) -> bool {
+        match self {
+            EntrySelector::Default => {
+                if name.ends_with('/') || name.contains("__MACOSX") || name.ends_with(".DS_Store") {
+                    return false;
+                }
+                if !name.ends_with(".json") || !name.contains("results/") {
+                    return false;
+                }
+                !name.contains("invalid-issues")
+            }
+            EntrySelector::Patterns { includes, excludes } => {
+                (includes.is_empty() || includes.is_match(name)) && !excludes.is_match(name)
+            }
+        }
+    }
+}
+
 #[wasm_bindgen]
-pub fn extract_jsons(zip_bytes: &[u8]) -> JsValue {
-    web_sys::console::log_1(&format!("[ZIP-RUST] Starting extraction, zip size: {} bytes", zip_bytes.len()).into());
+pub fn extract_jsons(zip_bytes: &[u8], patterns: Option<Vec<String>>) -> JsValue {
+    web_sys::console::log_1(
+        &format!(
+            "[ZIP-RUST] Starting extraction, zip size: {} bytes",
+            zip_bytes.len()
+        )
+        .into(),
+    );
 
     // Debug: Print first few bytes to verify zip signature
     if zip_bytes.len() >= 4 {
-        web_sys::console::log_1(&format!("[ZIP-RUST] Zip first 4 bytes: {:?}", &zip_bytes[0..4]).into());
-        if zip_bytes[0] != 0x50 || zip_bytes[1] != 0x4B || zip_bytes[2] != 0x03 || zip_bytes[3] != 0x04 {
+        web_sys::console::log_1(
+            &format!("[ZIP-RUST] Zip first 4 bytes: {:?}", &zip_bytes[0..4]).into(),
+        );
+        if zip_bytes[0] != 0x50
+            || zip_bytes[1] != 0x4B
+            || zip_bytes[2] != 0x03
+            || zip_bytes[3] != 0x04
+        {
             web_sys::console::log_1(&"[ZIP-RUST] ERROR: Invalid ZIP signature".into());
-            return serde_wasm_bindgen::to_value(&Vec::<Value>::new()).unwrap();
+            return serde_wasm_bindgen::to_value(&ExtractionResult {
+                entries: Vec::new(),
+                archive_error: Some("invalid ZIP signature".to_string()),
+            })
+            .unwrap();
         }
     }
 
+    let selector = match EntrySelector::from_patterns(patterns) {
+        Ok(selector) => selector,
+        Err(e) => {
+            web_sys::console::log_1(&format!("[ZIP-RUST] Invalid glob pattern: {:?}", e).into());
+            return serde_wasm_bindgen::to_value(&ExtractionResult {
+                entries: Vec::new(),
+                archive_error: Some(format!("invalid glob pattern: {}", e)),
+            })
+            .unwrap();
+        }
+    };
+
     let reader = Cursor::new(zip_bytes);
     let mut archive = match ZipArchive::new(reader) {
         Ok(archive) => {
-            web_sys::console::log_1(&format!("[ZIP-RUST] Successfully created ZipArchive with {} entries", archive.len()).into());
+            web_sys::console::log_1(
+                &format!(
+                    "[ZIP-RUST] Successfully created ZipArchive with {} entries",
+                    archive.len()
+                )
+                .into(),
+            );
             archive
-        },
+        }
         Err(e) => {
-            web_sys::console::log_1(&format!("[ZIP-RUST] Failed to create ZipArchive: {:?}", e).into());
-            return serde_wasm_bindgen::to_value(&Vec::<Value>::new()).unwrap();
-        },
+            web_sys::console::log_1(
+                &format!("[ZIP-RUST] Failed to create ZipArchive: {:?}", e).into(),
+            );
+            return serde_wasm_bindgen::to_value(&ExtractionResult {
+                entries: Vec::new(),
+                archive_error: Some(format!("failed to open ZIP archive: {}", e)),
+            })
+            .unwrap();
+        }
     };
 
-    // Create a single array to hold all JSON objects from all files
-    let mut all_json_strings = Vec::new();
+    // Create a single array to hold the extraction outcome for every matching file
+    let mut results: Vec<JsonEntry> = Vec::new();
 
     // Print all files in the zip for debugging
     web_sys::console::log_1(&"[ZIP-RUST] Listing all files in zip:".into());
@@ -49,73 +171,133 @@ pub fn extract_jsons(zip_bytes: &[u8]) -> JsValue {
         if let Ok(mut file) = archive.by_index(i) {
             let name = file.name().to_string();
 
-            // Skip directories and system files
-            if name.ends_with("/") || name.contains("__MACOSX") || name.ends_with(".DS_Store") {
+            if !selector.is_match(&name) {
                 continue;
             }
 
-            // Process JSON files from results directory
-            if name.ends_with(".json") && (name.contains("/results/") || name.contains("results/")) {
-                // Skip invalid-issues.json
-                if name.contains("invalid-issues") {
-                    continue;
-                }
+            web_sys::console::log_1(&format!("[ZIP-RUST] Processing JSON file: {}", name).into());
+
+            let uncompressed_size = file.size();
+            // `Unsupported` is deprecated in favor of the named constants, but it's
+            // the variant that carries the raw method code for anything this build
+            // doesn't have a feature-gated variant for (zstd, bzip2, AES, ...).
+            // `by_index` already rejects entries using such a method, so they are
+            // skipped above and never reach this match.
+            #[allow(deprecated)]
+            let compression_method = match file.compression() {
+                zip::CompressionMethod::Stored => 0u16,
+                zip::CompressionMethod::Deflated => 8u16,
+                zip::CompressionMethod::Unsupported(v) => v,
+                _ => 0u16,
+            };
+
+            // Read file contents
+            let mut contents = Vec::new();
+            let entry = match file.read_to_end(&mut contents) {
+                Ok(bytes_read) => {
+                    // Try to parse JSON
+                    match serde_json::from_slice::<Value>(&contents) {
+                        Ok(json) => {
+                            web_sys::console::log_1(
+                                &format!(
+                                    "[ZIP-RUST] Successfully parsed JSON from {} ({} bytes)",
+                                    name, bytes_read
+                                )
+                                .into(),
+                            );
 
-                web_sys::console::log_1(&format!("[ZIP-RUST] Processing JSON file: {}", name).into());
-
-                // Read file contents
-                let mut contents = Vec::new();
-                match file.read_to_end(&mut contents) {
-                    Ok(bytes_read) => {
-                        // Try to parse JSON
-                        match serde_json::from_slice::<Value>(&contents) {
-                            Ok(json) => {
-                                web_sys::console::log_1(&format!("[ZIP-RUST] Successfully parsed JSON from {} ({} bytes)",
-                                    name, bytes_read).into());
-
-                                // Print a sample of the JSON content for debugging
-                                let json_str = serde_json::to_string(&json).unwrap_or_default();
-                                let preview = if json_str.len() > 100 {
-                                    format!("{}...", &json_str[0..100])
-                                } else {
-                                    json_str
-                                };
-                                web_sys::console::log_1(&format!("[ZIP-RUST] JSON content preview: {}", preview).into());
-
-                                // Serialize each JSON object to a string
-                                let json_str = serde_json::to_string(&json).unwrap_or_default();
-                                all_json_strings.push(json_str);
-                            },
-                            Err(e) => {
-                                web_sys::console::log_1(&format!("[ZIP-RUST] Error parsing JSON from {}: {:?}", name, e).into());
-
-                                // Try to convert to string for better debugging
-                                match std::str::from_utf8(&contents) {
-                                    Ok(text) => {
-                                        let preview = if text.len() > 100 {
-                                            format!("{}...", &text[0..100])
-                                        } else {
-                                            text.to_string()
-                                        };
-                                        web_sys::console::log_1(&format!("[ZIP-RUST] Content preview: {}", preview).into());
-                                    },
-                                    Err(_) => {
-                                        web_sys::console::log_1(&"[ZIP-RUST] Content is not valid UTF-8".into());
-                                    }
-                                }
+                            JsonEntry {
+                                name: name.clone(),
+                                uncompressed_size,
+                                compression_method,
+                                parsed: Some(json),
+                                error: None,
+                            }
+                        }
+                        Err(e) => {
+                            web_sys::console::log_1(
+                                &format!("[ZIP-RUST] Error parsing JSON from {}: {:?}", name, e)
+                                    .into(),
+                            );
+
+                            JsonEntry {
+                                name: name.clone(),
+                                uncompressed_size,
+                                compression_method,
+                                parsed: None,
+                                error: Some(format!("invalid JSON: {}", e)),
                             }
                         }
-                    },
-                    Err(e) => {
-                        web_sys::console::log_1(&format!("[ZIP-RUST] Error reading file {}: {:?}", name, e).into());
                     }
                 }
-            }
+                Err(e) => {
+                    web_sys::console::log_1(
+                        &format!("[ZIP-RUST] Error reading file {}: {:?}", name, e).into(),
+                    );
+
+                    JsonEntry {
+                        name: name.clone(),
+                        uncompressed_size,
+                        compression_method,
+                        parsed: None,
+                        error: Some(format!("failed to read entry: {}", e)),
+                    }
+                }
+            };
+
+            results.push(entry);
         }
     }
 
-    web_sys::console::log_1(&format!("[ZIP-RUST] Extraction complete. Found {} JSON objects", all_json_strings.len()).into());
+    web_sys::console::log_1(
+        &format!(
+            "[ZIP-RUST] Extraction complete. Found {} matching entries",
+            results.len()
+        )
+        .into(),
+    );
+
+    serde_wasm_bindgen::to_value(&ExtractionResult {
+        entries: results,
+        archive_error: None,
+    })
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_selector_preserves_legacy_policy() {
+        let selector = EntrySelector::from_patterns(None).unwrap();
+        assert!(selector.is_match("results/run.json"));
+        assert!(!selector.is_match("results/invalid-issues.json"));
+        assert!(!selector.is_match("other/run.json"));
+        assert!(!selector.is_match("__MACOSX/results/run.json"));
+        assert!(!selector.is_match("results/.DS_Store"));
+    }
+
+    #[test]
+    fn combined_include_and_exclude_patterns() {
+        let selector = EntrySelector::from_patterns(Some(vec![
+            "results/**/*.json".to_string(),
+            "!**/invalid-*.json".to_string(),
+        ]))
+        .unwrap();
+
+        assert!(selector.is_match("results/nested/run.json"));
+        assert!(!selector.is_match("results/invalid-issues.json"));
+        assert!(!selector.is_match("other/run.json"));
+    }
+
+    #[test]
+    fn exclude_only_patterns_match_everything_except_excluded() {
+        let selector =
+            EntrySelector::from_patterns(Some(vec!["!**/invalid-*.json".to_string()])).unwrap();
 
-    // Return the array of JSON strings
-    serde_wasm_bindgen::to_value(&all_json_strings).unwrap()
+        assert!(selector.is_match("results/run.json"));
+        assert!(selector.is_match("anywhere/else.txt"));
+        assert!(!selector.is_match("results/invalid-issues.json"));
+    }
 }